@@ -6,8 +6,10 @@ pub enum SncfAPIError {
     ApiError(String),
 }
 
+#[derive(Debug, Clone, Copy)]
 pub enum Call {
     Ok,
+    Ko,
 }
 
 /// Calls the SNCF API simulation.
@@ -21,13 +23,16 @@ pub enum Call {
 /// assert!(call_me(Call::Ko).is_err());
 /// ```
 pub fn call_me(arg: Call) -> Result<(), SncfAPIError> {
-    // complete this function
-    todo!()
+    match arg {
+        Call::Ok => Ok(()),
+        Call::Ko => Err(SncfAPIError::ApiError("This call fails".to_string())),
+    }
 }
 
+/// Calls the SNCF API simulation twice, stopping at the first error.
 pub fn call_me_twice(arg: Call) -> Result<(), SncfAPIError> {
-    // complete this function
-    todo!()
+    call_me(arg)?;
+    call_me(arg)
 }
 
 #[cfg(test)]
@@ -49,4 +54,12 @@ mod tests {
         let err = result.expect_err("expected error for Call::Ko");
         assert_eq!(err.to_string(), "API error: This call fails");
     }
+
+    #[test]
+    fn call_me_twice_propagates_api_error() {
+        assert!(call_me_twice(Call::Ok).is_ok());
+
+        let err = call_me_twice(Call::Ko).expect_err("expected error for Call::Ko");
+        assert_eq!(err.to_string(), "API error: This call fails");
+    }
 }
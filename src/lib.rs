@@ -1,23 +1,144 @@
 use anyhow::bail;
-// Fix this import
-use sncf::{Call, call_me, call_me_twice};
+use sncf::{Call, SncfAPIError, call_me, call_me_twice};
+use thiserror::Error;
 
 pub const APPNAME: &str = env!("CARGO_PKG_NAME");
 
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_PKG_NAME"),
+    " [--help] [--demo] [--log-json]\n",
+    "\n",
+    "Options:\n",
+    "  -h, --help    Print this help and exit\n",
+    "  --demo        Run the sncf simulation without an API key\n",
+    "  --log-json    Write logs as JSON Lines\n",
+    "\n",
+    "Exit codes:\n",
+    "  0  Success\n",
+    "  2  Invalid command line arguments\n",
+    "  3  SNCF_API_KEY is not set\n",
+    "  4  SNCF_API_KEY is invalid\n",
+    "  5  SNCF API call failed\n",
+    "  6  Logging could not be set up",
+);
+
+/// Command line switches.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Flags {
+    /// Print the usage and exit.
+    pub help: bool,
+    /// Run the sncf simulation without an API key.
+    pub demo: bool,
+    /// Write logs as JSON Lines instead of plain text.
+    pub log_json: bool,
+}
+
+impl Flags {
+    /// Parses the arguments following the program name, rejecting unknown ones.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, StartupError> {
+        let mut flags = Flags::default();
+        for arg in args {
+            match arg.as_str() {
+                "-h" | "--help" => flags.help = true,
+                "--demo" => flags.demo = true,
+                "--log-json" => flags.log_json = true,
+                _ => return Err(StartupError::Usage(arg)),
+            }
+        }
+        Ok(flags)
+    }
+}
+
+/// Failures that stop the application at startup, each with its own exit code.
+#[derive(Error, Debug)]
+pub enum StartupError {
+    #[error("Cannot set up logging: {0}")]
+    Logging(anyhow::Error),
+    #[error("SNCF_API_KEY is not set, add it to your environment or .env file")]
+    MissingApiKey,
+    #[error("SNCF_API_KEY is invalid, set a valid key in your environment or .env file")]
+    InvalidApiKey(#[source] anyhow::Error),
+    #[error("SNCF API call failed: {0}")]
+    Api(anyhow::Error),
+    #[error("Unknown argument '{0}'\n{usage}", usage = USAGE)]
+    Usage(String),
+}
+
+impl StartupError {
+    /// Process exit code reported for this failure.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            StartupError::Usage(_) => 2,
+            StartupError::MissingApiKey => 3,
+            StartupError::InvalidApiKey(_) => 4,
+            StartupError::Api(_) => 5,
+            StartupError::Logging(_) => 6,
+        }
+    }
+}
+
+/// Runs the application for the given flags and SNCF_API_KEY value, if any.
+///
+/// Returns the lines to show to the user, which only demo mode produces.
+pub fn start(flags: &Flags, api_key: Option<String>) -> Result<Vec<String>, StartupError> {
+    tracing::info!("Application starting");
+
+    // Demo mode reports the sncf simulation results and does not need an API key
+    if flags.demo {
+        tracing::info!("Running in demo mode");
+        let report = demo_report();
+        for line in &report {
+            tracing::info!("{line}");
+        }
+        tracing::info!("Application ending");
+        return Ok(report);
+    }
+
+    let api_key = api_key.ok_or(StartupError::MissingApiKey)?;
+
+    // Validate the key before doing any work with it
+    api_check(api_key).map_err(StartupError::InvalidApiKey)?;
+
+    run().map_err(StartupError::Api)?;
+
+    tracing::info!("Application ending");
+    Ok(Vec::new())
+}
+
 pub fn run() -> anyhow::Result<()> {
     let arg1 = Call::Ok;
 
     call_me(arg1)?;
 
     let arg2 = Call::Ko;
-    // Fix me --v
-    let _ = call_me_twice(arg2);
-    let _ = call_me_twice(arg2);
+    // Call::Ko always fails, its errors are expected and not fatal to the run
+    for _ in 0..2 {
+        if let Err(err) = call_me_twice(arg2) {
+            tracing::warn!("{err}");
+        }
+    }
     Ok(())
 }
 
-// Fix me --v
-fn api_check(api: String) -> anyhow::Result<()> {
+/// Runs each simulated SNCF call and describes its outcome, one line per call.
+pub fn demo_report() -> Vec<String> {
+    type SimulatedCall = fn(Call) -> Result<(), SncfAPIError>;
+    let calls: [(&str, SimulatedCall); 2] =
+        [("call_me", call_me), ("call_me_twice", call_me_twice)];
+
+    calls
+        .into_iter()
+        .flat_map(|(name, call)| {
+            [Call::Ok, Call::Ko].map(|arg| match call(arg) {
+                Ok(()) => format!("{name}({arg:?}): ok"),
+                Err(err) => format!("{name}({arg:?}): {err}"),
+            })
+        })
+        .collect()
+}
+
+pub fn api_check(api: String) -> anyhow::Result<()> {
     match api.as_str() {
         "change_me" => Ok(()),
         _ => bail!("Wrong api key"),
@@ -45,6 +166,19 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn demo_report_shows_every_simulated_call() {
+        assert_eq!(
+            demo_report(),
+            [
+                "call_me(Ok): ok",
+                "call_me(Ko): API error: This call fails",
+                "call_me_twice(Ok): ok",
+                "call_me_twice(Ko): API error: This call fails",
+            ]
+        );
+    }
+
     #[test]
     fn api_check_accepts_expected_key() {
         let result = api_check("change_me".to_string());
@@ -59,4 +193,96 @@ mod tests {
         let err = result.expect_err("expected api_check to fail for invalid key");
         assert_eq!(err.to_string(), "Wrong api key");
     }
+
+    #[test]
+    fn start_without_api_key_fails() {
+        let err = start(&Flags::default(), None).expect_err("expected a missing key error");
+
+        assert!(matches!(err, StartupError::MissingApiKey));
+        assert_eq!(err.exit_code(), 3);
+    }
+
+    #[test]
+    fn start_with_wrong_api_key_fails() {
+        let err = start(&Flags::default(), Some("nope".to_string()))
+            .expect_err("expected an invalid key error");
+
+        assert!(matches!(err, StartupError::InvalidApiKey(_)));
+        assert_eq!(err.exit_code(), 4);
+        let source = std::error::Error::source(&err).expect("expected the api_check error");
+        assert_eq!(source.to_string(), "Wrong api key");
+    }
+
+    #[test]
+    fn start_with_valid_api_key_succeeds() {
+        let report = start(&Flags::default(), Some("change_me".to_string())).unwrap();
+
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn start_demo_does_not_need_api_key() {
+        let flags = Flags {
+            demo: true,
+            ..Flags::default()
+        };
+
+        let report = start(&flags, None).unwrap();
+
+        assert_eq!(report, demo_report());
+    }
+
+    #[test]
+    fn flags_parse_known_switches() {
+        let flags = Flags::parse(["--log-json".to_string(), "--demo".to_string()]).unwrap();
+
+        assert_eq!(
+            flags,
+            Flags {
+                demo: true,
+                log_json: true,
+                ..Flags::default()
+            }
+        );
+    }
+
+    #[test]
+    fn flags_reject_unknown_arguments() {
+        let err = Flags::parse(["--dmeo".to_string()]).expect_err("expected --dmeo to be rejected");
+
+        assert!(matches!(err, StartupError::Usage(ref arg) if arg == "--dmeo"));
+        assert_eq!(err.exit_code(), 2);
+        assert_eq!(
+            err.to_string(),
+            format!("Unknown argument '--dmeo'\n{USAGE}")
+        );
+    }
+
+    #[test]
+    fn usage_documents_every_exit_code() {
+        let errors = [
+            StartupError::Usage("--dmeo".to_string()),
+            StartupError::MissingApiKey,
+            StartupError::InvalidApiKey(anyhow::anyhow!("Wrong api key")),
+            StartupError::Api(anyhow::anyhow!("This call fails")),
+            StartupError::Logging(anyhow::anyhow!("No space left on device")),
+        ];
+
+        for err in errors {
+            let line = format!("\n  {}  ", err.exit_code());
+            assert!(
+                USAGE.contains(&line),
+                "exit code of {err:?} is not documented"
+            );
+        }
+    }
+
+    #[test]
+    fn flags_accept_help() {
+        for arg in ["-h", "--help"] {
+            let flags = Flags::parse([arg.to_string()]).unwrap();
+
+            assert!(flags.help);
+        }
+    }
 }
@@ -1,34 +1,96 @@
-use std::env;
+use std::{env, error::Error, fs, process::ExitCode};
 
-use async_rust_tui::{APPNAME, api_check, run};
+use async_rust_tui::{APPNAME, Flags, StartupError, USAGE, start};
+use tracing_appender::{
+    non_blocking::WorkerGuard,
+    rolling::{RollingFileAppender, Rotation},
+};
 use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
-fn main() -> anyhow::Result<()> {
-    // Setup logging
-    let file_appender = tracing_appender::rolling::daily("logs", format!("{}.log", APPNAME));
-    let (non_blocking_appender, _guard) = tracing_appender::non_blocking(file_appender);
+/// Number of daily log files kept before the oldest one is removed.
+///
+/// tracing-appender only rotates by time, so this bounds the number of files,
+/// not their size: a single day's file can grow without limit.
+const MAX_LOG_FILES: usize = 7;
 
-    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+fn main() -> ExitCode {
+    let result = Flags::parse(env::args().skip(1)).and_then(|flags| {
+        if flags.help {
+            println!("{USAGE}");
+            return Ok(());
+        }
+
+        // Keep the guard alive until the end so pending logs are flushed
+        let _guard = setup_logging(flags.log_json)?;
 
-    tracing_subscriber::registry()
-        .with(filter)
-        .with(
-            fmt::layer()
-                .with_writer(non_blocking_appender)
-                .with_ansi(false),
-        )
-        .init();
+        // Load env and API key
+        let _ = dotenvy::dotenv();
+        let api_key = env::var("SNCF_API_KEY").ok();
 
-    tracing::info!("Application starting");
-    // Load env and API key
-    let _ = dotenvy::dotenv();
-    let api_key = env::var("SNCF_API_KEY")?;
+        let report =
+            start(&flags, api_key).inspect_err(|err| tracing::error!("{}", error_chain(err)))?;
+        for line in report {
+            println!("{line}");
+        }
+        Ok(())
+    });
 
-    run()?;
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            ExitCode::from(err.exit_code())
+        }
+    }
+}
+
+/// Formats an error followed by its sources so the log keeps the root cause.
+fn error_chain(err: &StartupError) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        message.push_str(&format!(": {cause}"));
+        source = cause.source();
+    }
+    message
+}
+
+fn setup_logging(log_json: bool) -> Result<WorkerGuard, StartupError> {
+    // The directory must exist before old files can be pruned
+    fs::create_dir_all("logs").map_err(|err| StartupError::Logging(err.into()))?;
+
+    // JSON Lines go to their own files so each file holds a single format
+    let builder = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .max_log_files(MAX_LOG_FILES);
+    let builder = if log_json {
+        builder.filename_prefix(APPNAME).filename_suffix("jsonl")
+    } else {
+        builder.filename_prefix(format!("{}.log", APPNAME))
+    };
+    let file_appender = builder
+        .build("logs")
+        .map_err(|err| StartupError::Logging(err.into()))?;
+    let (non_blocking_appender, guard) = tracing_appender::non_blocking(file_appender);
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let registry = tracing_subscriber::registry().with(filter);
 
-    dbg!(&api_key);
-    api_check(api_key)?;
+    if log_json {
+        registry
+            .with(fmt::layer().json().with_writer(non_blocking_appender))
+            .try_init()
+            .map_err(|err| StartupError::Logging(err.into()))?;
+    } else {
+        registry
+            .with(
+                fmt::layer()
+                    .with_writer(non_blocking_appender)
+                    .with_ansi(false),
+            )
+            .try_init()
+            .map_err(|err| StartupError::Logging(err.into()))?;
+    }
 
-    tracing::info!("Application ending");
-    Ok(())
+    Ok(guard)
 }